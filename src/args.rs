@@ -1,4 +1,4 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use std::{env, path::PathBuf};
 
 fn default_keyboard_toml_path() -> PathBuf {
@@ -12,11 +12,87 @@ fn default_vial_json_path() -> PathBuf {
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 pub(crate) struct Args {
-    /// Path to the `keyboard.toml` file
-    #[arg(short, long, default_value=default_keyboard_toml_path().into_os_string())]
-    pub(crate) keyboard_toml_path: PathBuf,
+    #[command(subcommand)]
+    pub(crate) command: Commands,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum Commands {
+    /// Create a new RMK project from an existing `keyboard.toml` and `vial.json`
+    Create {
+        /// Path to the `keyboard.toml` file
+        #[arg(short, long, default_value=default_keyboard_toml_path().into_os_string())]
+        keyboard_toml_path: String,
+
+        /// Path to the `vial.json` file
+        #[arg(short, long, default_value=default_vial_json_path().into_os_string())]
+        vial_json_path: String,
+
+        /// Re-download the project template even if a cached copy is available
+        #[arg(long)]
+        refresh: bool,
+
+        /// Never access the network; fail if no cached template is available
+        #[arg(long, conflicts_with = "refresh")]
+        offline: bool,
+
+        /// Named template source from `rmk-cli.toml` to use instead of the default
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Override the source's `user/repo`, or `local:<path>` for a local template
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Override the source's branch/tag with a specific revision (commit SHA)
+        #[arg(long)]
+        rev: Option<String>,
+
+        /// List the steps this run would take without touching disk or the network
+        #[arg(long)]
+        dry_run: bool,
+
+        /// If keyboard.toml is missing or empty, open it in $EDITOR/$VISUAL
+        /// instead of aborting
+        #[arg(long)]
+        edit: bool,
+    },
+    /// Interactively scaffold a new RMK project
+    Init {
+        /// Name of the project to create
+        #[arg(default_value = "")]
+        project_name: String,
+
+        /// Target microcontroller chip
+        #[arg(short, long, default_value = "")]
+        chip: String,
+
+        /// Generate a split keyboard project
+        #[arg(short, long)]
+        split: bool,
+
+        /// Re-download the project template even if a cached copy is available
+        #[arg(long)]
+        refresh: bool,
+
+        /// Never access the network; fail if no cached template is available
+        #[arg(long, conflicts_with = "refresh")]
+        offline: bool,
+
+        /// Named template source from `rmk-cli.toml` to use instead of the default
+        #[arg(long)]
+        source: Option<String>,
+
+        /// Override the source's `user/repo`, or `local:<path>` for a local template
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Override the source's branch/tag with a specific revision (commit SHA)
+        #[arg(long)]
+        rev: Option<String>,
 
-    /// Path to the `vial.json` file
-    #[arg(short, long, default_value=default_vial_json_path().into_os_string())]
-    pub(crate) vial_json_path: PathBuf,
+        /// List the steps this run would take without touching disk or the network
+        #[arg(long)]
+        dry_run: bool,
+    },
 }
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// Conditional-request metadata persisted next to a cached template archive,
+/// so the next run can ask GitHub "has this changed?" instead of
+/// re-downloading the whole zip.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub(crate) struct CacheMeta {
+    pub(crate) etag: Option<String>,
+    pub(crate) last_modified: Option<String>,
+}
+
+/// A single cached template archive, keyed by `{user}-{repo}-{branch}` under
+/// the user's cache directory.
+pub(crate) struct TemplateCache {
+    pub(crate) zip_path: PathBuf,
+    meta_path: PathBuf,
+}
+
+impl TemplateCache {
+    pub(crate) fn new(user: &str, repo: &str, branch: &str) -> Result<Self, Box<dyn Error>> {
+        let cache_dir = dirs::cache_dir()
+            .ok_or("Could not determine user cache directory")?
+            .join("rmk-cli");
+        fs::create_dir_all(&cache_dir)?;
+        let key = format!("{}-{}-{}", user, repo, branch);
+        Ok(Self {
+            zip_path: cache_dir.join(format!("{}.zip", key)),
+            meta_path: cache_dir.join(format!("{}.json", key)),
+        })
+    }
+
+    pub(crate) fn exists(&self) -> bool {
+        self.zip_path.exists()
+    }
+
+    pub(crate) fn load_meta(&self) -> CacheMeta {
+        fs::read_to_string(&self.meta_path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn save_meta(&self, meta: &CacheMeta) -> Result<(), Box<dyn Error>> {
+        fs::write(&self.meta_path, serde_json::to_string(meta)?)?;
+        Ok(())
+    }
+
+    /// Move a freshly downloaded archive into place, replacing whatever was
+    /// cached before. Done last so a failed download never clobbers a
+    /// previously good cache entry.
+    pub(crate) fn store(&self, downloaded_zip: &PathBuf) -> Result<(), Box<dyn Error>> {
+        fs::rename(downloaded_zip, &self.zip_path)?;
+        Ok(())
+    }
+}
@@ -1,16 +1,166 @@
-use std::collections::HashMap;
+use reqwest::Client;
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
 
-pub fn get_board_chip_map() -> HashMap<&'static str, &'static str> {
-    let mut map = HashMap::new();
+use crate::config::{GitRef, TemplateSource};
 
-    // Nordic boards
-    map.insert("nrfmicro", "nrf52840");
-    map.insert("bluemicro840", "nrf52840");
-    map.insert("puchi_ble", "nrf52840");
-    map.insert("nice!nano", "nrf52840");
-    map.insert("nice!nano_v2", "nrf52840");
-    map.insert("XIAO BLE", "nrf52840");
+/// Built-in chip list, used when we can't reach the template source
+/// (offline, a network error, or a source that has never been listed
+/// before) and have no cached listing either.
+const FALLBACK_CHIPS: &[&str] = &["nrf52840", "rp2040", "esp32c3", "esp32s3", "stm32f4"];
 
+#[derive(Debug, Deserialize)]
+struct ContentEntry {
+    name: String,
+    #[serde(rename = "type")]
+    kind: String,
+}
 
-    map
-}
\ No newline at end of file
+/// List the chip/board targets a template source actually provides.
+///
+/// For a remote source this queries the GitHub contents API for the
+/// template repo's top-level directories, caching the result next to the
+/// template zip cache so `--offline` runs (or a flaky network) still get a
+/// menu. For a `local:` source it just reads the directory. `*_split`
+/// directories are folded into their non-split counterpart, matching how
+/// `remote_folder` is built from `chip`/`split` elsewhere.
+pub(crate) async fn get_chip_options(
+    source: &TemplateSource,
+    offline: bool,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let (user, repo, git_ref) = match source {
+        TemplateSource::Local(path) => return list_local_chip_options(path),
+        TemplateSource::Remote {
+            user,
+            repo,
+            git_ref,
+        } => (user, repo, git_ref),
+    };
+
+    let cache_path = chip_listing_cache_path(user, repo, git_ref);
+
+    if offline {
+        return Ok(load_cached_listing(cache_path.as_deref()).unwrap_or_else(fallback_chips));
+    }
+
+    match discover_remote_chip_options(user, repo, git_ref).await {
+        Ok(chips) => {
+            if let Some(path) = &cache_path {
+                let _ = fs::write(path, serde_json::to_string(&chips)?);
+            }
+            Ok(chips)
+        }
+        Err(e) => {
+            eprintln!(
+                "Warning: failed to list available chips from {}/{} ({}), falling back to a cached or built-in list",
+                user, repo, e
+            );
+            Ok(load_cached_listing(cache_path.as_deref()).unwrap_or_else(fallback_chips))
+        }
+    }
+}
+
+async fn discover_remote_chip_options(
+    user: &str,
+    repo: &str,
+    git_ref: &GitRef,
+) -> Result<Vec<String>, Box<dyn Error>> {
+    let git_ref_param = match git_ref {
+        GitRef::Branch(branch) => branch.clone(),
+        GitRef::Tag(tag) => tag.clone(),
+        GitRef::Rev(rev) => rev.clone(),
+    };
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/contents?ref={}",
+        user, repo, git_ref_param
+    );
+
+    let client = Client::new();
+    let response = client
+        .get(&url)
+        .header("User-Agent", "rmk-cli")
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(format!("GitHub contents API returned {}", response.status()).into());
+    }
+
+    let entries: Vec<ContentEntry> = response.json().await?;
+    Ok(fold_split_variants(
+        entries
+            .into_iter()
+            .filter(|entry| entry.kind == "dir" && !entry.name.starts_with('.'))
+            .map(|entry| entry.name),
+    ))
+}
+
+fn list_local_chip_options(base_path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    let names = fs::read_dir(base_path)?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| !name.starts_with('.'));
+    Ok(fold_split_variants(names))
+}
+
+fn fold_split_variants(names: impl Iterator<Item = String>) -> Vec<String> {
+    let mut chips: Vec<String> = names
+        .map(|name| {
+            name.strip_suffix("_split")
+                .map(str::to_owned)
+                .unwrap_or(name)
+        })
+        .collect();
+    chips.sort();
+    chips.dedup();
+    chips
+}
+
+fn fallback_chips() -> Vec<String> {
+    FALLBACK_CHIPS.iter().map(|s| s.to_string()).collect()
+}
+
+fn chip_listing_cache_path(user: &str, repo: &str, git_ref: &GitRef) -> Option<PathBuf> {
+    let git_ref_key = match git_ref {
+        GitRef::Branch(branch) => branch.clone(),
+        GitRef::Tag(tag) => format!("tag-{}", tag),
+        GitRef::Rev(rev) => format!("rev-{}", rev),
+    };
+    let cache_dir = dirs::cache_dir()?.join("rmk-cli");
+    fs::create_dir_all(&cache_dir).ok()?;
+    Some(cache_dir.join(format!("{}-{}-{}-chips.json", user, repo, git_ref_key)))
+}
+
+fn load_cached_listing(path: Option<&Path>) -> Option<Vec<String>> {
+    let content = fs::read_to_string(path?).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(values: &[&str]) -> impl Iterator<Item = String> {
+        values.iter().map(|s| s.to_string()).collect::<Vec<_>>().into_iter()
+    }
+
+    #[test]
+    fn folds_split_variants_into_their_base_chip() {
+        let chips = fold_split_variants(names(&["nrf52840", "nrf52840_split", "rp2040"]));
+        assert_eq!(chips, vec!["nrf52840", "rp2040"]);
+    }
+
+    #[test]
+    fn sorts_and_dedupes() {
+        let chips = fold_split_variants(names(&["rp2040", "esp32c3", "rp2040"]));
+        assert_eq!(chips, vec!["esp32c3", "rp2040"]);
+    }
+
+    #[test]
+    fn leaves_non_split_names_untouched() {
+        let chips = fold_split_variants(names(&["stm32f4"]));
+        assert_eq!(chips, vec!["stm32f4"]);
+    }
+}
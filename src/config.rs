@@ -0,0 +1,237 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+/// One named template source, as declared under `[source.<name>]` in
+/// `rmk-cli.toml`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub(crate) struct SourceConfig {
+    pub(crate) repo: Option<String>,
+    pub(crate) branch: Option<String>,
+    pub(crate) tag: Option<String>,
+    pub(crate) rev: Option<String>,
+    pub(crate) path: Option<PathBuf>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct Config {
+    #[serde(default)]
+    pub(crate) source: HashMap<String, SourceConfig>,
+}
+
+impl Config {
+    /// Load `./rmk-cli.toml`, falling back to `~/.config/rmk-cli/config.toml`.
+    /// Neither file existing isn't an error: callers just get an empty
+    /// config and fall back to the built-in default source.
+    pub(crate) fn load() -> Result<Self, Box<dyn Error>> {
+        if let Some(config) = Self::read(&PathBuf::from("rmk-cli.toml"))? {
+            return Ok(config);
+        }
+        if let Some(config_dir) = dirs::config_dir() {
+            if let Some(config) = Self::read(&config_dir.join("rmk-cli").join("config.toml"))? {
+                return Ok(config);
+            }
+        }
+        Ok(Config::default())
+    }
+
+    fn read(path: &PathBuf) -> Result<Option<Config>, Box<dyn Error>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = fs::read_to_string(path)?;
+        Ok(Some(toml::from_str(&content)?))
+    }
+}
+
+/// A fully resolved template location: either a GitHub repo pinned to a
+/// branch/tag/revision, or a local directory to copy from (`local:`
+/// sources, or a source config's `path`).
+#[derive(Debug, Clone)]
+pub(crate) enum TemplateSource {
+    Remote {
+        user: String,
+        repo: String,
+        git_ref: GitRef,
+    },
+    Local(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum GitRef {
+    Branch(String),
+    Tag(String),
+    Rev(String),
+}
+
+impl Default for TemplateSource {
+    fn default() -> Self {
+        TemplateSource::Remote {
+            user: "HaoboGu".to_string(),
+            repo: "rmk-template".to_string(),
+            git_ref: GitRef::Branch("feat/rework".to_string()),
+        }
+    }
+}
+
+/// Resolve the template source to use, applying (highest priority first)
+/// the `--repo`/`--rev` overrides, the named `--source` from the config
+/// file, and finally the built-in default (`HaoboGu/rmk-template` on
+/// `feat/rework`).
+pub(crate) fn resolve_source(
+    config: &Config,
+    source_name: Option<&str>,
+    repo_override: Option<&str>,
+    rev_override: Option<&str>,
+) -> Result<TemplateSource, Box<dyn Error>> {
+    let mut source = match source_name {
+        Some(name) => config
+            .source
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("No source named '{}' in rmk-cli.toml", name))?,
+        None => config.source.get("default").cloned().unwrap_or_default(),
+    };
+
+    if let Some(repo) = repo_override {
+        source.path = None;
+        source.repo = Some(repo.to_string());
+    }
+    if let Some(rev) = rev_override {
+        source.tag = None;
+        source.branch = None;
+        source.rev = Some(rev.to_string());
+    }
+
+    if let Some(path) = source.path {
+        return Ok(TemplateSource::Local(path));
+    }
+
+    match source.repo {
+        Some(repo) if repo.starts_with("local:") => {
+            Ok(TemplateSource::Local(PathBuf::from(&repo[6..])))
+        }
+        Some(repo) => {
+            let (user, repo_name) = repo
+                .split_once('/')
+                .ok_or("Source `repo` must be in `user/name` form")?;
+            let git_ref = if let Some(rev) = source.rev {
+                GitRef::Rev(rev)
+            } else if let Some(tag) = source.tag {
+                GitRef::Tag(tag)
+            } else if let Some(branch) = source.branch {
+                GitRef::Branch(branch)
+            } else {
+                GitRef::Branch("main".to_string())
+            };
+            Ok(TemplateSource::Remote {
+                user: user.to_string(),
+                repo: repo_name.to_string(),
+                git_ref,
+            })
+        }
+        None => {
+            let mut default = TemplateSource::default();
+            if let TemplateSource::Remote { git_ref, .. } = &mut default {
+                if let Some(rev) = source.rev {
+                    *git_ref = GitRef::Rev(rev);
+                } else if let Some(tag) = source.tag {
+                    *git_ref = GitRef::Tag(tag);
+                } else if let Some(branch) = source.branch {
+                    *git_ref = GitRef::Branch(branch);
+                }
+            }
+            Ok(default)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with(name: &str, source: SourceConfig) -> Config {
+        let mut config = Config::default();
+        config.source.insert(name.to_string(), source);
+        config
+    }
+
+    #[test]
+    fn falls_back_to_built_in_default_with_no_config_or_overrides() {
+        let source = resolve_source(&Config::default(), None, None, None).unwrap();
+        assert!(matches!(source, TemplateSource::Remote { repo, .. } if repo == "rmk-template"));
+    }
+
+    #[test]
+    fn uses_named_config_source_when_requested() {
+        let config = config_with(
+            "mine",
+            SourceConfig {
+                repo: Some("someone/else".to_string()),
+                ..Default::default()
+            },
+        );
+        let source = resolve_source(&config, Some("mine"), None, None).unwrap();
+        assert!(
+            matches!(source, TemplateSource::Remote { user, repo, .. } if user == "someone" && repo == "else")
+        );
+    }
+
+    #[test]
+    fn errors_on_unknown_named_source() {
+        let result = resolve_source(&Config::default(), Some("missing"), None, None);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn repo_override_wins_over_config_repo_and_clears_path() {
+        let config = config_with(
+            "default",
+            SourceConfig {
+                repo: Some("config/repo".to_string()),
+                path: Some(PathBuf::from("/some/local/template")),
+                ..Default::default()
+            },
+        );
+        let source = resolve_source(&config, None, Some("someone/else"), None).unwrap();
+        assert!(
+            matches!(source, TemplateSource::Remote { user, repo, .. } if user == "someone" && repo == "else")
+        );
+    }
+
+    #[test]
+    fn rev_override_wins_over_config_tag_and_branch() {
+        let config = config_with(
+            "default",
+            SourceConfig {
+                repo: Some("someone/else".to_string()),
+                tag: Some("v1".to_string()),
+                branch: Some("main".to_string()),
+                ..Default::default()
+            },
+        );
+        let source = resolve_source(&config, None, None, Some("deadbeef")).unwrap();
+        assert!(matches!(
+            source,
+            TemplateSource::Remote {
+                git_ref: GitRef::Rev(rev),
+                ..
+            } if rev == "deadbeef"
+        ));
+    }
+
+    #[test]
+    fn local_prefixed_repo_becomes_a_local_source() {
+        let config = config_with(
+            "default",
+            SourceConfig {
+                repo: Some("local:/some/path".to_string()),
+                ..Default::default()
+            },
+        );
+        let source = resolve_source(&config, None, None, None).unwrap();
+        assert!(matches!(source, TemplateSource::Local(path) if path == PathBuf::from("/some/path")));
+    }
+}
@@ -0,0 +1,49 @@
+use std::error::Error;
+use std::fs;
+
+use crate::keyboard_toml::parse_keyboard_toml;
+
+const SKELETON: &str = r#"# keyboard.toml describes the board this project targets.
+# See https://github.com/HaoboGu/rmk for the full schema.
+
+[keyboard]
+name = "my_keyboard"
+chip = "nrf52840"
+# split = true
+
+# [matrix]
+# row_pins = ["P0_00", "P0_01"]
+# col_pins = ["P0_02", "P0_03"]
+"#;
+
+/// Open `path` in the user's `$EDITOR`/`$VISUAL`, seeding it with a
+/// commented skeleton if it's missing or empty, then re-parse the result
+/// with [`parse_keyboard_toml`] in a loop: on a parse error, the error is
+/// prepended as a comment and the editor reopens until the file is valid.
+pub(crate) fn edit_keyboard_toml(path: &str) -> Result<(), Box<dyn Error>> {
+    let existing = fs::read_to_string(path).unwrap_or_default();
+    let mut content = if existing.trim().is_empty() {
+        SKELETON.to_string()
+    } else {
+        existing
+    };
+
+    loop {
+        content = edit::edit(&content)?;
+        fs::write(path, &content)?;
+
+        match parse_keyboard_toml(path) {
+            Ok(_) => return Ok(()),
+            Err(e) => {
+                eprintln!("keyboard.toml is invalid, reopening editor: {}", e);
+                let commented_error = e
+                    .to_string()
+                    .lines()
+                    .map(|l| format!("# {l}"))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                content = format!("{}\n{}", commented_error, content);
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::config::TemplateSource;
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct KeyboardToml {
+    pub(crate) keyboard: KeyboardSection,
+    #[serde(default)]
+    pub(crate) matrix: Option<MatrixSection>,
+}
+
+#[derive(Debug, Deserialize)]
+pub(crate) struct KeyboardSection {
+    pub(crate) name: String,
+    pub(crate) chip: String,
+    #[serde(default)]
+    pub(crate) split: bool,
+}
+
+/// Pin definitions for the key matrix, used to sanity-check the generated
+/// keymap's dimensions against what the board can actually scan.
+#[derive(Debug, Deserialize)]
+pub(crate) struct MatrixSection {
+    pub(crate) row_pins: Vec<String>,
+    pub(crate) col_pins: Vec<String>,
+}
+
+/// Read and parse a `keyboard.toml` file without reducing it to a
+/// [`ProjectInfo`] yet, for callers that need more than the project name
+/// and chip (e.g. the keymap generator's pin definitions).
+pub(crate) fn read_keyboard_toml(path: &str) -> Result<KeyboardToml, Box<dyn Error>> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read keyboard.toml at '{}': {}", path, e))?;
+    Ok(toml::from_str(&content)?)
+}
+
+/// Everything the generation pipeline needs to know about the project being
+/// created: where it lives on disk, which template folder to pull, and
+/// where to pull it from.
+pub(crate) struct ProjectInfo {
+    pub(crate) project_name: String,
+    pub(crate) target_dir: PathBuf,
+    pub(crate) remote_folder: String,
+    pub(crate) source: TemplateSource,
+}
+
+/// Parse a `keyboard.toml` file into a [`ProjectInfo`], using the built-in
+/// default template source. Callers that support `--source`/`--repo`/`--rev`
+/// overrides should replace `project_info.source` afterwards.
+pub(crate) fn parse_keyboard_toml(path: &str) -> Result<ProjectInfo, Box<dyn Error>> {
+    let parsed = read_keyboard_toml(path)?;
+
+    let remote_folder = if parsed.keyboard.split {
+        format!("{}_split", parsed.keyboard.chip)
+    } else {
+        parsed.keyboard.chip.clone()
+    };
+
+    Ok(ProjectInfo {
+        project_name: parsed.keyboard.name.clone(),
+        target_dir: PathBuf::from(&parsed.keyboard.name),
+        remote_folder,
+        source: TemplateSource::default(),
+    })
+}
@@ -0,0 +1,294 @@
+use genco::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+
+use crate::keyboard_toml::read_keyboard_toml;
+
+#[derive(Debug, Deserialize)]
+struct VialJson {
+    matrix: MatrixDims,
+    layouts: Layouts,
+    layers: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct MatrixDims {
+    rows: usize,
+    cols: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct Layouts {
+    keymap: Vec<Vec<String>>,
+}
+
+/// Parse `vial.json` (and the matrix pins in `keyboard.toml`) and emit a
+/// `src/keymap.rs` populated with the board's default keymap, so projects
+/// start from a real keymap instead of a file that still needs hand-editing.
+pub(crate) fn write_keymap_rs(
+    target_dir: &Path,
+    keyboard_toml_path: &str,
+    vial_json_path: &str,
+) -> Result<(), Box<dyn Error>> {
+    let vial: VialJson = serde_json::from_str(
+        &fs::read_to_string(vial_json_path)
+            .map_err(|e| format!("Failed to read vial.json at '{}': {}", vial_json_path, e))?,
+    )?;
+
+    let keyboard = read_keyboard_toml(keyboard_toml_path)?;
+    if let Some(matrix) = &keyboard.matrix {
+        if matrix.row_pins.len() != vial.matrix.rows || matrix.col_pins.len() != vial.matrix.cols {
+            eprintln!(
+                "Warning: keyboard.toml declares a {}x{} matrix but vial.json describes {}x{}",
+                matrix.row_pins.len(),
+                matrix.col_pins.len(),
+                vial.matrix.rows,
+                vial.matrix.cols
+            );
+        }
+    }
+
+    let source = generate_keymap_source(&vial)?;
+    fs::write(target_dir.join("src").join("keymap.rs"), source)?;
+    Ok(())
+}
+
+fn generate_keymap_source(vial: &VialJson) -> Result<String, Box<dyn Error>> {
+    let row = vial.matrix.rows;
+    let col = vial.matrix.cols;
+    let num_layer = vial.layers.max(1);
+
+    if vial.layouts.keymap.len() != row {
+        return Err(format!(
+            "vial.json declares a {}x{} matrix but layouts.keymap has {} row(s)",
+            row,
+            col,
+            vial.layouts.keymap.len()
+        )
+        .into());
+    }
+    if let Some(bad_row) = vial.layouts.keymap.iter().find(|keys| keys.len() != col) {
+        return Err(format!(
+            "vial.json declares a {}x{} matrix but found a row with {} key(s)",
+            row,
+            col,
+            bad_row.len()
+        )
+        .into());
+    }
+
+    let base_layer: Vec<Vec<String>> = vial
+        .layouts
+        .keymap
+        .iter()
+        .map(|keys| keys.iter().map(|code| keycode_token(code)).collect())
+        .collect();
+
+    // `vial.json` only describes the physical layout (layer 0); higher
+    // layers start out transparent so the board is usable the moment it's
+    // flashed, with the holes from the physical layout preserved.
+    let mut layers: Vec<Vec<Vec<&str>>> = Vec::with_capacity(num_layer);
+    for layer_index in 0..num_layer {
+        let rows = base_layer
+            .iter()
+            .map(|row_codes| {
+                row_codes
+                    .iter()
+                    .map(|code| {
+                        if layer_index == 0 || code.as_str() == "no!()" {
+                            code.as_str()
+                        } else {
+                            "a!(Transparent)"
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        layers.push(rows);
+    }
+
+    let macros = used_macros(&layers);
+    let import_list = macros.join(", ");
+
+    let tokens: rust::Tokens = quote! {
+        use rmk::action::KeyAction;
+        use rmk::{$import_list};
+
+        pub const ROW: usize = $row;
+        pub const COL: usize = $col;
+        pub const NUM_LAYER: usize = $num_layer;
+
+        pub fn get_default_keymap() -> [[[KeyAction; COL]; ROW]; NUM_LAYER] {
+            [
+                $(for layer_rows in &layers join (,) =>
+                    layer!([
+                        $(for row_cells in layer_rows join (,) =>
+                            [$(for cell in row_cells join (, ) => $(*cell))]
+                        )
+                    ])
+                )
+            ]
+        }
+    };
+
+    Ok(tokens.to_file_string()?)
+}
+
+/// Determine which of `rmk`'s keymap macros the generated body actually
+/// calls, so the emitted `use` only names what's used and the scaffolded
+/// project doesn't trip `unused_imports` under `-D warnings`. `layer!` is
+/// always used since every layer is wrapped in one.
+fn used_macros(layers: &[Vec<Vec<&str>>]) -> Vec<&'static str> {
+    let (mut uses_a, mut uses_k, mut uses_mo, mut uses_no) = (false, false, false, false);
+    for cell in layers.iter().flatten().flatten() {
+        if cell.starts_with("a!(") {
+            uses_a = true;
+        } else if cell.starts_with("k!(") {
+            uses_k = true;
+        } else if cell.starts_with("mo!(") {
+            uses_mo = true;
+        } else if cell.starts_with("no!(") {
+            uses_no = true;
+        }
+    }
+
+    let mut macros = Vec::new();
+    if uses_a {
+        macros.push("a");
+    }
+    if uses_k {
+        macros.push("k");
+    }
+    macros.push("layer");
+    if uses_mo {
+        macros.push("mo");
+    }
+    if uses_no {
+        macros.push("no");
+    }
+    macros
+}
+
+/// Map a VIA/Vial keycode string (e.g. `KC_A`, `MO(1)`, `KC_TRNS`) to the
+/// RMK macro call that produces the equivalent [`KeyAction`]. Matrix holes
+/// (`-1,-1` in `layouts.keymap`, or an empty/`KC_NO` entry) become `no!()`.
+fn keycode_token(code: &str) -> String {
+    if code.is_empty() || code == "-1,-1" || code == "KC_NO" {
+        return "no!()".to_string();
+    }
+    if code == "KC_TRNS" {
+        return "a!(Transparent)".to_string();
+    }
+    if let Some(layer) = code
+        .strip_prefix("MO(")
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        return format!("mo!({})", layer);
+    }
+    if let Some(ident) = keycode_lookup().get(code) {
+        return format!("k!({})", ident);
+    }
+    // Unrecognized keycode: fall back to stripping the `KC_` prefix verbatim
+    // rather than failing the whole scaffold; the user can fix it up by hand.
+    match code.strip_prefix("KC_") {
+        Some(ident) => format!("k!({})", ident),
+        None => format!("k!({})", code),
+    }
+}
+
+fn keycode_lookup() -> &'static HashMap<&'static str, &'static str> {
+    use std::sync::OnceLock;
+    static LOOKUP: OnceLock<HashMap<&'static str, &'static str>> = OnceLock::new();
+    LOOKUP.get_or_init(|| {
+        let mut m = HashMap::new();
+        m.insert("KC_ESC", "Escape");
+        m.insert("KC_TAB", "Tab");
+        m.insert("KC_SPC", "Space");
+        m.insert("KC_ENT", "Enter");
+        m.insert("KC_BSPC", "Backspace");
+        m.insert("KC_DEL", "Delete");
+        m.insert("KC_LSFT", "LShift");
+        m.insert("KC_RSFT", "RShift");
+        m.insert("KC_LCTL", "LCtrl");
+        m.insert("KC_RCTL", "RCtrl");
+        m.insert("KC_LALT", "LAlt");
+        m.insert("KC_RALT", "RAlt");
+        m.insert("KC_LGUI", "LGui");
+        m.insert("KC_RGUI", "RGui");
+        m.insert("KC_MINS", "Minus");
+        m.insert("KC_EQL", "Equal");
+        m.insert("KC_LBRC", "LeftBracket");
+        m.insert("KC_RBRC", "RightBracket");
+        m.insert("KC_BSLS", "Backslash");
+        m.insert("KC_SCLN", "Semicolon");
+        m.insert("KC_QUOT", "Quote");
+        m.insert("KC_GRV", "Grave");
+        m.insert("KC_COMM", "Comma");
+        m.insert("KC_DOT", "Dot");
+        m.insert("KC_SLSH", "Slash");
+        m.insert("KC_CAPS", "CapsLock");
+        m.insert("KC_UP", "Up");
+        m.insert("KC_DOWN", "Down");
+        m.insert("KC_LEFT", "Left");
+        m.insert("KC_RGHT", "Right");
+        m.insert("KC_1", "Kc1");
+        m.insert("KC_2", "Kc2");
+        m.insert("KC_3", "Kc3");
+        m.insert("KC_4", "Kc4");
+        m.insert("KC_5", "Kc5");
+        m.insert("KC_6", "Kc6");
+        m.insert("KC_7", "Kc7");
+        m.insert("KC_8", "Kc8");
+        m.insert("KC_9", "Kc9");
+        m.insert("KC_0", "Kc0");
+        m
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keycode_token_maps_holes_to_no() {
+        assert_eq!(keycode_token(""), "no!()");
+        assert_eq!(keycode_token("-1,-1"), "no!()");
+        assert_eq!(keycode_token("KC_NO"), "no!()");
+    }
+
+    #[test]
+    fn keycode_token_maps_transparent() {
+        assert_eq!(keycode_token("KC_TRNS"), "a!(Transparent)");
+    }
+
+    #[test]
+    fn keycode_token_maps_layer_momentary() {
+        assert_eq!(keycode_token("MO(1)"), "mo!(1)");
+    }
+
+    #[test]
+    fn keycode_token_maps_known_keycode() {
+        assert_eq!(keycode_token("KC_ESC"), "k!(Escape)");
+    }
+
+    #[test]
+    fn keycode_token_falls_back_to_stripped_kc_prefix_for_unknown_codes() {
+        assert_eq!(keycode_token("KC_WEIRD"), "k!(WEIRD)");
+        assert_eq!(keycode_token("SOME_OTHER"), "k!(SOME_OTHER)");
+    }
+
+    #[test]
+    fn used_macros_always_includes_layer_and_only_whats_emitted() {
+        let layers = vec![vec![vec!["k!(A)", "no!()"]]];
+        assert_eq!(used_macros(&layers), vec!["k", "layer", "no"]);
+    }
+
+    #[test]
+    fn used_macros_omits_unused_ones() {
+        let layers = vec![vec![vec!["a!(Transparent)"]]];
+        assert_eq!(used_macros(&layers), vec!["a", "layer"]);
+    }
+}
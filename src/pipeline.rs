@@ -0,0 +1,461 @@
+use futures::stream::StreamExt;
+use reqwest::header::{HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use std::error::Error;
+use std::fs;
+use std::fs::File;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use zip::ZipArchive;
+
+use crate::cache::{CacheMeta, TemplateCache};
+use crate::config::{GitRef, TemplateSource};
+use crate::keyboard_toml::ProjectInfo;
+use crate::keymap_gen::write_keymap_rs;
+
+/// Shared state threaded through every step of a generation run.
+pub(crate) struct Context {
+    pub(crate) project_info: ProjectInfo,
+    pub(crate) keyboard_toml_path: String,
+    pub(crate) vial_json_path: Option<String>,
+    pub(crate) refresh: bool,
+    pub(crate) offline: bool,
+}
+
+/// A single unit of work in a project generation run.
+///
+/// Each step can report whether it has already been completed via
+/// [`Step::is_done`], so re-invoking an interrupted pipeline skips whatever
+/// already happened instead of wiping everything and starting over.
+pub(crate) enum Step {
+    FetchTemplate,
+    ExtractFolder,
+    CopyFile { from: PathBuf, to: PathBuf },
+    RenderPlaceholders,
+    GenerateKeymap,
+    RunPostCmd { program: String, args: Vec<String> },
+}
+
+impl Step {
+    fn name(&self) -> String {
+        match self {
+            Step::FetchTemplate => "Fetch template".to_string(),
+            Step::ExtractFolder => "Extract template folder".to_string(),
+            Step::CopyFile { to, .. } => format!("Copy {}", to.display()),
+            Step::RenderPlaceholders => "Render project name placeholders".to_string(),
+            Step::GenerateKeymap => "Generate src/keymap.rs from vial.json".to_string(),
+            Step::RunPostCmd { program, args } => format!("Run `{} {}`", program, args.join(" ")),
+        }
+    }
+
+    fn is_done(&self, ctx: &Context) -> bool {
+        match self {
+            // A remote source always runs `fetch_template_step`: that's what
+            // performs the conditional `If-None-Match`/`If-Modified-Since`
+            // revalidation (or the `--offline`/`--refresh` overrides) on
+            // every invocation. Treating an existing cached zip as "done"
+            // would skip revalidation forever after the first fetch.
+            Step::FetchTemplate => matches!(ctx.project_info.source, TemplateSource::Local(_)),
+            Step::ExtractFolder => ctx.project_info.target_dir.join("Cargo.toml").exists(),
+            Step::CopyFile { to, .. } => to.exists(),
+            Step::RenderPlaceholders => false,
+            Step::GenerateKeymap => ctx.project_info.target_dir.join("src/keymap.rs").exists(),
+            Step::RunPostCmd { .. } => false,
+        }
+    }
+
+    async fn invoke(&self, ctx: &Context) -> Result<(), Box<dyn Error>> {
+        match self {
+            Step::FetchTemplate => fetch_template_step(ctx).await,
+            Step::ExtractFolder => extract_folder_step(ctx).await,
+            Step::CopyFile { from, to } => {
+                fs::copy(from, to)?;
+                Ok(())
+            }
+            Step::RenderPlaceholders => render_placeholders_step(ctx),
+            Step::GenerateKeymap => {
+                let vial_json_path = ctx
+                    .vial_json_path
+                    .as_deref()
+                    .ok_or("GenerateKeymap requires a vial.json path")?;
+                write_keymap_rs(
+                    &ctx.project_info.target_dir,
+                    &ctx.keyboard_toml_path,
+                    vial_json_path,
+                )
+            }
+            Step::RunPostCmd { program, args } => {
+                let status = Command::new(program)
+                    .args(args)
+                    .current_dir(&ctx.project_info.target_dir)
+                    .status()?;
+                if !status.success() {
+                    return Err(format!(
+                        "`{} {}` exited with {}",
+                        program,
+                        args.join(" "),
+                        status
+                    )
+                    .into());
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// An ordered list of [`Step`]s making up one `create`/`init` run.
+///
+/// Template repos can append their own steps (currently only post-generation
+/// commands) by declaring a `[[steps]]` section in an `rmk-template.toml`
+/// at the root of the extracted folder; once [`Step::ExtractFolder`]
+/// completes, those are appended to the end of the run.
+pub(crate) struct Pipeline {
+    steps: Vec<Step>,
+}
+
+impl Pipeline {
+    pub(crate) fn new(steps: Vec<Step>) -> Self {
+        Self { steps }
+    }
+
+    pub(crate) async fn run(&mut self, ctx: &Context, dry_run: bool) -> Result<(), Box<dyn Error>> {
+        if dry_run {
+            for step in &self.steps {
+                println!("[dry-run] {}", step.name());
+            }
+            println!("[dry-run] (a template's own [[steps]] commands, if any, would run after the steps above)");
+            return Ok(());
+        }
+
+        let mut i = 0;
+        while i < self.steps.len() {
+            if self.steps[i].is_done(ctx) {
+                println!("✓ {} (already done)", self.steps[i].name());
+            } else {
+                println!("→ {}", self.steps[i].name());
+                self.steps[i].invoke(ctx).await?;
+            }
+
+            // Queue a template's own post-generation steps at the end of the
+            // run once the extraction step is satisfied, whether it just ran
+            // or a previous, interrupted run already completed it.
+            if matches!(self.steps[i], Step::ExtractFolder) {
+                self.steps
+                    .extend(load_extra_steps(&ctx.project_info.target_dir));
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TemplateMetadata {
+    #[serde(default)]
+    steps: Vec<TemplateStepConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TemplateStepConfig {
+    cmd: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+/// Load extra post-generation commands (e.g. `cargo fmt`) that a template
+/// declares for itself in `rmk-template.toml`. Missing or unparsable
+/// metadata just means "no extra steps", not an error.
+fn load_extra_steps(target_dir: &Path) -> Vec<Step> {
+    let Ok(content) = fs::read_to_string(target_dir.join("rmk-template.toml")) else {
+        return Vec::new();
+    };
+    let Ok(metadata) = toml::from_str::<TemplateMetadata>(&content) else {
+        return Vec::new();
+    };
+    metadata
+        .steps
+        .into_iter()
+        .map(|s| Step::RunPostCmd {
+            program: s.cmd,
+            args: s.args,
+        })
+        .collect()
+}
+
+/// Resolve a remote source to its download URL and cache entry. Returns
+/// `None` for a local source, which has nothing to fetch over the network.
+fn remote_target(
+    source: &TemplateSource,
+) -> Result<Option<(String, TemplateCache)>, Box<dyn Error>> {
+    match source {
+        TemplateSource::Local(_) => Ok(None),
+        TemplateSource::Remote {
+            user,
+            repo,
+            git_ref,
+        } => {
+            let (url, ref_key) = match git_ref {
+                GitRef::Branch(branch) => (
+                    format!(
+                        "https://github.com/{}/{}/archive/refs/heads/{}.zip",
+                        user, repo, branch
+                    ),
+                    branch.clone(),
+                ),
+                GitRef::Tag(tag) => (
+                    format!(
+                        "https://github.com/{}/{}/archive/refs/tags/{}.zip",
+                        user, repo, tag
+                    ),
+                    format!("tag-{}", tag),
+                ),
+                GitRef::Rev(rev) => (
+                    format!("https://github.com/{}/{}/archive/{}.zip", user, repo, rev),
+                    format!("rev-{}", rev),
+                ),
+            };
+            let cache = TemplateCache::new(user, repo, &ref_key)?;
+            Ok(Some((url, cache)))
+        }
+    }
+}
+
+async fn fetch_template_step(ctx: &Context) -> Result<(), Box<dyn Error>> {
+    match remote_target(&ctx.project_info.source)? {
+        None => Ok(()),
+        Some((url, cache)) => {
+            if ctx.offline {
+                if !cache.exists() {
+                    return Err(
+                        "--offline was given but no cached template is available; run once without it"
+                            .into(),
+                    );
+                }
+                println!("Using cached project template (offline)...");
+                Ok(())
+            } else {
+                fetch_with_revalidation(&url, &cache, ctx.refresh).await
+            }
+        }
+    }
+}
+
+/// Populate `cache` with an up-to-date copy of `download_url`, downloading
+/// only when necessary. Unless `refresh` is set, this sends
+/// `If-None-Match`/`If-Modified-Since` from the cache's stored metadata; a
+/// `304 Not Modified` response leaves the cached zip untouched. The
+/// downloaded archive only replaces the cache once it's fully downloaded,
+/// so a failed download never deletes a previously good cache entry.
+async fn fetch_with_revalidation(
+    download_url: &str,
+    cache: &TemplateCache,
+    refresh: bool,
+) -> Result<(), Box<dyn Error>> {
+    let client = Client::new();
+    let mut request = client.get(download_url);
+
+    if !refresh && cache.exists() {
+        let meta = cache.load_meta();
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = meta
+            .etag
+            .as_deref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = meta
+            .last_modified
+            .as_deref()
+            .and_then(|v| HeaderValue::from_str(v).ok())
+        {
+            headers.insert(IF_MODIFIED_SINCE, last_modified);
+        }
+        request = request.headers(headers);
+    }
+
+    println!("Checking project template for updates...");
+    let response = request.send().await?;
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        println!("Template is up to date, using cached copy...");
+        return Ok(());
+    }
+    if !response.status().is_success() {
+        return Err(format!("Download failed: {}", response.status()).into());
+    }
+
+    let new_meta = CacheMeta {
+        etag: response
+            .headers()
+            .get(reqwest::header::ETAG)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned),
+        last_modified: response
+            .headers()
+            .get(reqwest::header::LAST_MODIFIED)
+            .and_then(|v| v.to_str().ok())
+            .map(str::to_owned),
+    };
+
+    // Stream into a temp file next to the cache so a partial/failed
+    // download never clobbers the existing cached archive.
+    let cache_dir = cache
+        .zip_path
+        .parent()
+        .ok_or("Cache path has no parent directory")?;
+    let temp_file_path = cache_dir.join("download.tmp.zip");
+    let mut temp_file = File::create(&temp_file_path)?;
+
+    struct TempFileCleanup<'a> {
+        path: &'a Path,
+    }
+    impl<'a> Drop for TempFileCleanup<'a> {
+        fn drop(&mut self) {
+            if self.path.exists() {
+                if let Err(e) = fs::remove_file(self.path) {
+                    eprintln!(
+                        "Failed to remove temp file '{}': {}",
+                        self.path.display(),
+                        e
+                    );
+                }
+            }
+        }
+    }
+    let _cleanup_guard = TempFileCleanup {
+        path: &temp_file_path,
+    };
+
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        temp_file.write_all(&chunk)?;
+    }
+    drop(temp_file);
+
+    cache.store(&temp_file_path)?;
+    cache.save_meta(&new_meta)?;
+
+    Ok(())
+}
+
+async fn extract_folder_step(ctx: &Context) -> Result<(), Box<dyn Error>> {
+    let output_path = &ctx.project_info.target_dir;
+    let folder = &ctx.project_info.remote_folder;
+
+    match remote_target(&ctx.project_info.source)? {
+        None => {
+            let TemplateSource::Local(base_path) = &ctx.project_info.source else {
+                return Err("Local template source missing its path".into());
+            };
+            copy_local_template(base_path, output_path, folder)
+        }
+        Some((_, cache)) => extract_zip(&cache.zip_path, output_path, folder),
+    }
+}
+
+/// Extract the subdirectory `folder` from a downloaded template zip into
+/// `output_path`.
+fn extract_zip(zip_path: &Path, output_path: &Path, folder: &str) -> Result<(), Box<dyn Error>> {
+    if output_path.exists() {
+        fs::remove_dir_all(output_path)?;
+    }
+    fs::create_dir_all(output_path)?;
+
+    let zip_file = File::open(zip_path)?;
+    let mut zip = ZipArchive::new(zip_file)?;
+
+    let mut folder_found = false;
+    for i in 0..zip.len() {
+        let mut file = zip.by_index(i)?;
+        let file_name = file.enclosed_name().ok_or("Invalid file path")?;
+
+        // Find the root directory from the ZIP file
+        let segments: Vec<_> = file_name.iter().collect();
+        if segments.len() > 1 && segments[1] == folder {
+            folder_found = true;
+            let relative_name = file_name.iter().skip(2).collect::<PathBuf>();
+            let out_path = output_path.join(relative_name);
+
+            if file.is_dir() {
+                fs::create_dir_all(&out_path)?;
+            } else {
+                if let Some(parent) = out_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                let mut outfile = File::create(&out_path)?;
+                io::copy(&mut file, &mut outfile)?;
+            }
+        }
+    }
+
+    if !folder_found {
+        return Err(format!(
+            "The specified chip/board '{}' does not exist in the template",
+            folder
+        )
+        .into());
+    }
+
+    println!("Project created, path: {}", output_path.display());
+    Ok(())
+}
+
+/// Copy a template straight from a local directory (`local:<path>` sources
+/// or a config entry's `path`), skipping the network and cache entirely.
+fn copy_local_template(
+    base_path: &Path,
+    output_path: &Path,
+    folder: &str,
+) -> Result<(), Box<dyn Error>> {
+    let source_dir = base_path.join(folder);
+    if !source_dir.exists() {
+        return Err(format!(
+            "The specified chip/board '{}' does not exist in local template at {}",
+            folder,
+            base_path.display()
+        )
+        .into());
+    }
+
+    if output_path.exists() {
+        fs::remove_dir_all(output_path)?;
+    }
+    fs::create_dir_all(output_path)?;
+
+    for entry in walkdir::WalkDir::new(&source_dir) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(&source_dir)?;
+        let dest = output_path.join(relative);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&dest)?;
+        } else {
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(entry.path(), &dest)?;
+        }
+    }
+
+    println!("Project created, path: {}", output_path.display());
+    Ok(())
+}
+
+fn render_placeholders_step(ctx: &Context) -> Result<(), Box<dyn Error>> {
+    println!("Replacing project name placeholders...");
+    let walker = walkdir::WalkDir::new(&ctx.project_info.target_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().map_or(false, |ext| ext == "toml"));
+    for entry in walker {
+        let path = entry.path();
+        let content = fs::read_to_string(path)?;
+        let new_content = content.replace("{{ project_name }}", &ctx.project_info.project_name);
+        fs::write(path, new_content)?;
+    }
+    Ok(())
+}